@@ -3,10 +3,30 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use smol::lock::RwLock;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
 use std::io::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use surf::http::auth::BasicAuth;
+
+use crate::error::DomoErr;
+
+/// Safety buffer subtracted from a token's reported `expires_in` so a
+/// request in flight doesn't race the token expiring mid-call.
+const TOKEN_EXPIRY_SKEW: Duration = Duration::from_secs(30);
+
+/// Skew-adjusted duration a freshly fetched token stays valid for.
+fn expiry_duration(expires_in: u32) -> Duration {
+    Duration::from_secs(u64::from(expires_in)).saturating_sub(TOKEN_EXPIRY_SKEW)
+}
+
+/// Whether a cached token's expiry instant is still ahead of `now`.
+fn is_token_valid(expires_at: Instant, now: Instant) -> bool {
+    now < expires_at
+}
 
 /// Domo auth token
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -36,12 +56,68 @@ pub struct DomoScope {
     pub workflow: bool,
 }
 
+impl DomoScope {
+    /// Emit every enabled scope as a single `%20`-joined query param, in the
+    /// shape `request_access_token` puts on the OAuth URL.
+    pub fn to_query_param(&self) -> String {
+        let mut scopes = Vec::new();
+        if self.data {
+            scopes.push("data");
+        }
+        if self.user {
+            scopes.push("user");
+        }
+        if self.audit {
+            scopes.push("audit");
+        }
+        if self.dashboard {
+            scopes.push("dashboard");
+        }
+        if self.buzz {
+            scopes.push("buzz");
+        }
+        if self.account {
+            scopes.push("account");
+        }
+        if self.workflow {
+            scopes.push("workflow");
+        }
+        scopes.join("%20")
+    }
+
+    fn has(&self, scope: RequiredScope) -> bool {
+        match scope {
+            RequiredScope::Data => self.data,
+            RequiredScope::User => self.user,
+            RequiredScope::Audit => self.audit,
+            RequiredScope::Dashboard => self.dashboard,
+            RequiredScope::Buzz => self.buzz,
+            RequiredScope::Account => self.account,
+            RequiredScope::Workflow => self.workflow,
+        }
+    }
+}
+
+/// A scope a request can require; mirrors the flags on [`DomoScope`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequiredScope {
+    Data,
+    User,
+    Audit,
+    Dashboard,
+    Buzz,
+    Account,
+    Workflow,
+}
+
 /// Object to use to store/retrieve access tokens for Domo API.
 pub struct DomoClientAppCredentials {
     pub client_id: String,
     pub client_secret: String,
     pub token: Option<DomoToken>,
     pub domo_scope: DomoScope,
+    /// Cached token and the `Instant` it expires at.
+    cached_token: Arc<RwLock<Option<(DomoToken, Instant)>>>,
 }
 
 impl DomoToken {
@@ -108,6 +184,7 @@ impl DomoClientAppCredentials {
                 client_secret,
                 token: None,
                 domo_scope: scope,
+                cached_token: Arc::new(RwLock::new(None)),
             }
         } else {
             let scope = DomoScope {
@@ -124,6 +201,7 @@ impl DomoClientAppCredentials {
                 client_secret,
                 token: None,
                 domo_scope: scope,
+                cached_token: Arc::new(RwLock::new(None)),
             }
         }
     }
@@ -213,35 +291,45 @@ impl DomoClientAppCredentials {
         }
     }
 
-    fn request_access_token(&self) -> Option<DomoToken> {
-        let mut payload = HashMap::new();
-        payload.insert("grant_type", "client_credentials");
-        let mut scopes = "".to_string();
-        if self.domo_scope.data {
-            if !scopes.is_empty() {
-                scopes += &"%20".to_string()
-            }
-            scopes += &"data".to_string();
-        }
-        if self.domo_scope.user {
-            if !scopes.is_empty() {
-                scopes += &"%20".to_string()
-            }
-            scopes += &"user".to_string();
+    /// Get a still-valid access token, re-authenticating if needed.
+    /// A token set with [`DomoClientAppCredentials::token_info`] is returned
+    /// as is; its expiry is not checked and it is never refreshed.
+    pub async fn get_token(&self) -> Result<String, DomoErr> {
+        if let Some(token) = &self.token {
+            return Ok(token.access_token.clone());
         }
-        if self.domo_scope.audit {
-            if !scopes.is_empty() {
-                scopes += &"%20".to_string()
+
+        {
+            let cached = self.cached_token.read().await;
+            if let Some((token, expires_at)) = cached.as_ref() {
+                if is_token_valid(*expires_at, Instant::now()) {
+                    return Ok(token.access_token.clone());
+                }
             }
-            scopes += &"audit".to_string();
         }
-        if self.domo_scope.dashboard {
-            if !scopes.is_empty() {
-                scopes += &"%20".to_string()
+
+        let mut cached = self.cached_token.write().await;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if is_token_valid(*expires_at, Instant::now()) {
+                return Ok(token.access_token.clone());
             }
-            scopes += &"dashboard".to_string();
         }
 
+        let params = self.scope_params();
+        let token = self
+            .fetch_access_token_async(&self.client_id, &self.client_secret, &params)
+            .await?;
+        let expires_at = Instant::now() + expiry_duration(token.expires_in);
+        let access_token = token.access_token.clone();
+        *cached = Some((token, expires_at));
+        Ok(access_token)
+    }
+
+    fn request_access_token(&self) -> Option<DomoToken> {
+        let mut payload = HashMap::new();
+        payload.insert("grant_type", "client_credentials");
+        let scopes = self.scope_params();
+
         if let Some(token) = self.fetch_access_token(&self.client_id, &self.client_secret, &scopes)
         {
             Some(token)
@@ -250,6 +338,24 @@ impl DomoClientAppCredentials {
         }
     }
 
+    /// Build the `%20`-joined scope query param from the currently enabled
+    /// [`DomoScope`] flags.
+    fn scope_params(&self) -> String {
+        self.domo_scope.to_query_param()
+    }
+
+    /// Error if `scope` isn't enabled on this client's [`DomoScope`].
+    pub fn require_scope(&self, scope: RequiredScope) -> Result<(), DomoErr> {
+        if self.domo_scope.has(scope) {
+            Ok(())
+        } else {
+            Err(DomoErr(format!(
+                "this request requires the `{:?}` scope, which isn't enabled on this DomoClientAppCredentials's DomoScope",
+                scope
+            )))
+        }
+    }
+
     fn fetch_access_token(
         &self,
         client_id: &str,
@@ -258,6 +364,34 @@ impl DomoClientAppCredentials {
     ) -> Option<DomoToken> {
         fetch_access_token(client_id, client_secret, params)
     }
+
+    async fn fetch_access_token_async(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        params: &str,
+    ) -> Result<DomoToken, DomoErr> {
+        let url = format!(
+            "https://api.domo.com/oauth/token?grant_type=client_credentials&scope={}",
+            params
+        );
+        let auth = BasicAuth::new(client_id, client_secret);
+        let mut response = surf::post(url)
+            .header(auth.name(), auth.value())
+            .await
+            .map_err(|e| DomoErr(format!("token request failed: {}", e)))?;
+        if response.status().is_success() {
+            response
+                .body_json::<DomoToken>()
+                .await
+                .map_err(|e| DomoErr(format!("failed to parse token response: {}", e)))
+        } else {
+            Err(DomoErr(format!(
+                "Error getting Domo Token: {}",
+                response.status()
+            )))
+        }
+    }
 }
 
 fn fetch_access_token(client_id: &str, client_secret: &str, params: &str) -> Option<DomoToken> {
@@ -287,3 +421,33 @@ fn fetch_access_token(client_id: &str, client_secret: &str, params: &str) -> Opt
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_duration_does_not_panic_when_expires_in_is_shorter_than_skew() {
+        assert_eq!(expiry_duration(0), Duration::ZERO);
+        assert_eq!(expiry_duration(10), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_expiry_duration_subtracts_skew() {
+        assert_eq!(expiry_duration(3600), Duration::from_secs(3600) - TOKEN_EXPIRY_SKEW);
+    }
+
+    #[test]
+    fn test_token_far_in_future_is_valid() {
+        let now = Instant::now();
+        let expires_at = now + expiry_duration(3600);
+        assert!(is_token_valid(expires_at, now));
+    }
+
+    #[test]
+    fn test_token_within_skew_is_not_valid() {
+        let now = Instant::now();
+        let expires_at = now + expiry_duration(10);
+        assert!(!is_token_valid(expires_at, now));
+    }
+}
@@ -1,7 +1,12 @@
+use std::io::{Read, Write};
 use std::sync::Arc;
 
-use crate::{DomoApi, domo::dataset::{Dataset, DatasetQueryData}, error::DomoErr, util::csv::serialize_csv_str};
-use serde::Serialize;
+use crate::{DomoApi, auth::RequiredScope, domo::dataset::{Dataset, DatasetQueryData}, error::DomoErr, util::csv::serialize_csv_str};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 pub struct DatasetApiBuilder {
@@ -13,12 +18,14 @@ impl DatasetApiBuilder {
         DatasetApiListBuilder::new(self.client)
     }
     pub async fn info(self, dataset_id: &str) -> Result<Dataset, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.client.auth.require_scope(RequiredScope::Data)?;
         let token = self.client.auth.get_token().await?;
         let req = surf::get(format!("https://api.domo.com/v1/datasets/{}", dataset_id)).header("Authorization", format!("Bearer {}", token));
         let s = self.client.client.send(req).await?.body_json().await?;
         Ok(s)
     }
     pub async fn delete(self, dataset_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.client.auth.require_scope(RequiredScope::Data)?;
         let token = self.client.auth.get_token().await?;
         let req = surf::delete(format!("https://api.domo.com/v1/datasets/{}", dataset_id)).header("Authorization", format!("Bearer {}", token));
         let s = self.client.client.send(req).await?;
@@ -37,19 +44,421 @@ impl DatasetApiBuilder {
     pub fn upload(self, dataset_id: &str) -> DatasetApiUploadBuilder {
         DatasetApiUploadBuilder::new(self.client, dataset_id)
     }
-    // pub fn create()
-    // pub fn modify()
-    // pub fn pdp()
-    // pdp_policy_info
-    // add pdp policy
-    // modify pdp policy
-    // delete pdp policy
-    // list pdp policies
+    pub fn create(self) -> DatasetApiCreateBuilder {
+        DatasetApiCreateBuilder::new(self.client)
+    }
+    pub fn modify(self, dataset_id: &str) -> DatasetApiModifyBuilder {
+        DatasetApiModifyBuilder::new(self.client, dataset_id)
+    }
+    pub fn list_policies(self, dataset_id: &str) -> DatasetApiPdpListBuilder {
+        DatasetApiPdpListBuilder::new(self.client, dataset_id)
+    }
+    pub fn policy_info(self, dataset_id: &str, policy_id: &str) -> DatasetApiPdpInfoBuilder {
+        DatasetApiPdpInfoBuilder::new(self.client, dataset_id, policy_id)
+    }
+    pub fn add_policy(self, dataset_id: &str) -> DatasetApiPdpAddBuilder {
+        DatasetApiPdpAddBuilder::new(self.client, dataset_id)
+    }
+    pub fn modify_policy(self, dataset_id: &str, policy_id: &str) -> DatasetApiPdpModifyBuilder {
+        DatasetApiPdpModifyBuilder::new(self.client, dataset_id, policy_id)
+    }
+    pub fn delete_policy(self, dataset_id: &str, policy_id: &str) -> DatasetApiPdpDeleteBuilder {
+        DatasetApiPdpDeleteBuilder::new(self.client, dataset_id, policy_id)
+    }
+}
+
+/// A Personalized Data Permissions (PDP) policy: binds a set of users and
+/// groups to a row-filter predicate so only matching rows are visible to
+/// them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdpPolicy {
+    /// `None` when building a policy to pass to `add_policy()`; Domo
+    /// assigns the id once the policy is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub policy_type: String,
+    pub users: Vec<u64>,
+    pub groups: Vec<u64>,
+    pub filters: Vec<PdpFilter>,
+}
+
+/// A single row-filter predicate within a [`PdpPolicy`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdpFilter {
+    pub column: String,
+    pub operator: PdpFilterOperator,
+    pub values: Vec<String>,
+    #[serde(default)]
+    pub not: bool,
+}
+
+/// Comparison used by a [`PdpFilter`] when matching a column's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PdpFilterOperator {
+    #[serde(rename = "EQUALS")]
+    Equals,
+    #[serde(rename = "GREATER_THAN")]
+    GreaterThan,
+    #[serde(rename = "CONTAINS")]
+    Contains,
+}
+
+pub struct DatasetApiPdpListBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+}
+impl DatasetApiPdpListBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+        }
+    }
+    pub async fn execute(&self) -> Result<Vec<PdpPolicy>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let req = surf::get(format!("https://api.domo.com/v1/datasets/{}/policies", self.dataset_id)).header("Authorization", format!("Bearer {}", token));
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
+}
+
+pub struct DatasetApiPdpInfoBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+    policy_id: String,
+}
+impl DatasetApiPdpInfoBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str, policy_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+            policy_id: policy_id.to_string(),
+        }
+    }
+    pub async fn execute(&self) -> Result<PdpPolicy, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let req = surf::get(format!("https://api.domo.com/v1/datasets/{}/policies/{}", self.dataset_id, self.policy_id)).header("Authorization", format!("Bearer {}", token));
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
+}
+
+pub struct DatasetApiPdpAddBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+    policy: Option<PdpPolicy>,
+}
+impl DatasetApiPdpAddBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+            policy: None,
+        }
+    }
+    pub fn policy(&mut self, policy: PdpPolicy) -> &mut Self {
+        self.policy = Some(policy);
+        self
+    }
+    pub async fn execute(&self) -> Result<PdpPolicy, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let policy = self.policy.as_ref().ok_or(DomoErr("No policy was set to add".to_string()))?;
+        let req = surf::post(format!("https://api.domo.com/v1/datasets/{}/policies", self.dataset_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .body(json!(policy));
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
+}
+
+pub struct DatasetApiPdpModifyBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+    policy_id: String,
+    policy: Option<PdpPolicy>,
+}
+impl DatasetApiPdpModifyBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str, policy_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+            policy_id: policy_id.to_string(),
+            policy: None,
+        }
+    }
+    pub fn policy(&mut self, policy: PdpPolicy) -> &mut Self {
+        self.policy = Some(policy);
+        self
+    }
+    pub async fn execute(&self) -> Result<PdpPolicy, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let policy = self.policy.as_ref().ok_or(DomoErr("No policy was set to modify".to_string()))?;
+        let req = surf::put(format!("https://api.domo.com/v1/datasets/{}/policies/{}", self.dataset_id, self.policy_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .body(json!(policy));
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
+}
+
+pub struct DatasetApiPdpDeleteBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+    policy_id: String,
+}
+impl DatasetApiPdpDeleteBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str, policy_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+            policy_id: policy_id.to_string(),
+        }
+    }
+    pub async fn execute(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let req = surf::delete(format!("https://api.domo.com/v1/datasets/{}/policies/{}", self.dataset_id, self.policy_id)).header("Authorization", format!("Bearer {}", token));
+        let res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err(Box::new(DomoErr(res.status().canonical_reason().into())))
+        }
+    }
+}
+/// A dataset's column layout, in the order the columns should appear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schema {
+    pub columns: Vec<Column>,
+}
+
+/// A single column in a [`Schema`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+}
+
+/// The Domo column types accepted when creating or modifying a dataset's
+/// [`Schema`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnType {
+    #[serde(rename = "STRING")]
+    String,
+    #[serde(rename = "LONG")]
+    Long,
+    #[serde(rename = "DECIMAL")]
+    Decimal,
+    #[serde(rename = "DOUBLE")]
+    Double,
+    #[serde(rename = "DATE")]
+    Date,
+    #[serde(rename = "DATETIME")]
+    DateTime,
+}
+
+/// Infer a [`Schema`] from a sample of rows, reusing the same CSV
+/// serialization path as [`DatasetApiUploadBuilder::data`] for the column
+/// names, and guessing each column's [`ColumnType`] from its values (Long if
+/// every value parses as an integer, Double if every value parses as a
+/// float, String otherwise).
+fn infer_schema<T: Serialize>(sample: &[T]) -> Result<Schema, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    #[derive(Clone, Copy)]
+    enum Inferred {
+        Long,
+        Double,
+        String,
+    }
+
+    let csv_str = serialize_csv_str(sample, true)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(csv_str.as_bytes());
+    let names: Vec<String> = reader.headers()?.iter().map(|name| name.to_string()).collect();
+    if names.is_empty() {
+        return Err(Box::new(DomoErr("Sample was empty; cannot infer a schema".to_string())));
+    }
+    let mut inferred = vec![Inferred::Long; names.len()];
+
+    for record in reader.records() {
+        let record = record?;
+        for (column, value) in record.iter().enumerate().take(inferred.len()) {
+            inferred[column] = match inferred[column] {
+                Inferred::String => Inferred::String,
+                Inferred::Long if value.parse::<i64>().is_ok() => Inferred::Long,
+                Inferred::Long | Inferred::Double if value.parse::<f64>().is_ok() => Inferred::Double,
+                _ => Inferred::String,
+            };
+        }
+    }
+
+    let columns = names
+        .into_iter()
+        .zip(inferred)
+        .map(|(name, kind)| Column {
+            name,
+            column_type: match kind {
+                Inferred::Long => ColumnType::Long,
+                Inferred::Double => ColumnType::Double,
+                Inferred::String => ColumnType::String,
+            },
+        })
+        .collect();
+
+    Ok(Schema { columns })
+}
+
+pub struct DatasetApiCreateBuilder {
+    api: Arc<DomoApi>,
+    name: Option<String>,
+    description: Option<String>,
+    schema: Option<Schema>,
+}
+impl DatasetApiCreateBuilder {
+    pub fn new(client: Arc<DomoApi>) -> Self {
+        Self {
+            api: client,
+            name: None,
+            description: None,
+            schema: None,
+        }
+    }
+
+    pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description<S: Into<String>>(&mut self, description: S) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn schema(&mut self, schema: Schema) -> &mut Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Infer the [`Schema`] from a sample of rows.
+    pub fn schema_from_sample<T: Serialize>(&mut self, sample: &[T]) -> Result<&mut Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.schema = Some(infer_schema(sample)?);
+        Ok(self)
+    }
+
+    pub async fn execute(&self) -> Result<Dataset, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let name = self.name.as_ref().ok_or(DomoErr("No name was set for the dataset".to_string()))?;
+        let schema = self.schema.as_ref().ok_or(DomoErr("No schema was set for the dataset".to_string()))?;
+        let body = json!({
+            "name": name,
+            "description": self.description,
+            "rows": 0,
+            "schema": schema,
+        });
+        let req = surf::post("https://api.domo.com/v1/datasets")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(body);
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
+}
+
+pub struct DatasetApiModifyBuilder {
+    api: Arc<DomoApi>,
+    dataset_id: String,
+    name: Option<String>,
+    description: Option<String>,
+    schema: Option<Schema>,
+}
+impl DatasetApiModifyBuilder {
+    pub fn new(client: Arc<DomoApi>, dataset_id: &str) -> Self {
+        Self {
+            api: client,
+            dataset_id: dataset_id.to_string(),
+            name: None,
+            description: None,
+            schema: None,
+        }
+    }
+
+    pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description<S: Into<String>>(&mut self, description: S) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn schema(&mut self, schema: Schema) -> &mut Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub async fn execute(&self) -> Result<Dataset, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
+        let token = self.api.auth.get_token().await?;
+        let mut body = serde_json::Map::new();
+        if let Some(name) = &self.name {
+            body.insert("name".to_string(), json!(name));
+        }
+        if let Some(description) = &self.description {
+            body.insert("description".to_string(), json!(description));
+        }
+        if let Some(schema) = &self.schema {
+            body.insert("schema".to_string(), json!(schema));
+        }
+        if body.is_empty() {
+            return Err(Box::new(DomoErr(
+                "No name, description, or schema was set to modify".to_string(),
+            )));
+        }
+        let req = surf::put(format!("https://api.domo.com/v1/datasets/{}", self.dataset_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .body(serde_json::Value::Object(body));
+        let mut res = self.api.client.send(req).await?;
+        if res.status().is_success() {
+            Ok(res.body_json().await?)
+        } else {
+            Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
+        }
+    }
 }
+
 pub struct DatasetApiUploadBuilder {
     api: Arc<DomoApi>,
     dataset_id: String,
     data: Option<String>,
+    gzip: bool,
 }
 impl DatasetApiUploadBuilder {
     pub fn new(client: Arc<DomoApi>, dataset_id: &str) -> Self {
@@ -57,6 +466,7 @@ impl DatasetApiUploadBuilder {
             api: client,
             dataset_id: dataset_id.to_string(),
             data: None,
+            gzip: false,
         }
     }
 
@@ -70,13 +480,29 @@ impl DatasetApiUploadBuilder {
         self
     }
 
+    /// Compress the CSV body with gzip before uploading, setting
+    /// `Content-Encoding: gzip` while keeping `Content-Type: text/csv`.
+    pub fn gzip(&mut self) -> &mut Self {
+        self.gzip = true;
+        self
+    }
+
     pub async fn execute(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
         let token = self.api.auth.get_token().await?;
         let body = self.data.as_ref().ok_or(DomoErr("No Data was set to upload".to_string()))?;
         let req = surf::put(format!("https://api.domo.com/v1/datasets/{}/data", self.dataset_id))
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "text/csv")
-            .body(body.to_string());
+            .header("Content-Type", "text/csv");
+        let req = if self.gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body.as_bytes())?;
+            let compressed = encoder.finish()?;
+            req.header("Content-Encoding", "gzip")
+                .body(surf::Body::from_bytes(compressed))
+        } else {
+            req.body(body.to_string())
+        };
         let mut res = self.api.client.send(req).await?;
         if res.status().is_success() {
             Ok(())
@@ -99,12 +525,19 @@ impl DatasetApiQueryDataBuilder {
         }
     }
     pub async fn execute(&self) -> Result<DatasetQueryData, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
         let token = self.api.auth.get_token().await?;
         let body = json!({ "sql": self.sql_query });
-        let req = surf::post(format!("https://api.domo.com/v1/datasets/query/execute/{}", self.dataset_id)).header("Authorization", format!("Bearer {}", token)).body(body);
+        let req = surf::post(format!("https://api.domo.com/v1/datasets/query/execute/{}", self.dataset_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept-Encoding", "gzip")
+            .body(body);
         let mut res = self.api.client.send(req).await?;
         if res.status().is_success() {
-            Ok(res.body_json().await?)
+            let is_gzip = response_is_gzip(&res);
+            let bytes = res.body_bytes().await?;
+            let bytes = if is_gzip { gunzip(&bytes)? } else { bytes };
+            Ok(serde_json::from_slice(&bytes)?)
         } else {
             Err(Box::new(DomoErr(format!("{}: {}", res.status().canonical_reason(), res.body_string().await.unwrap_or_default()))))
         }
@@ -133,13 +566,34 @@ impl DatasetApiGetDataBuilder {
         self
     }
     pub async fn execute(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
         let token = self.api.auth.get_token().await?;
-        let req = surf::get(format!("https://api.domo.com/v1/datasets/{}/data", self.dataset_id)).query(self)?.header("Authorization", format!("Bearer {}", token));
-        let s = self.api.client.send(req).await?.body_bytes().await?;
-        Ok(s)
+        let req = surf::get(format!("https://api.domo.com/v1/datasets/{}/data", self.dataset_id))
+            .query(self)?
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept-Encoding", "gzip");
+        let mut res = self.api.client.send(req).await?;
+        let is_gzip = response_is_gzip(&res);
+        let bytes = res.body_bytes().await?;
+        if is_gzip { gunzip(&bytes) } else { Ok(bytes) }
     }
 }
 
+/// Whether a response declared a gzip `Content-Encoding`.
+fn response_is_gzip(res: &surf::Response) -> bool {
+    res.header("Content-Encoding")
+        .map(|values| values.as_str().eq_ignore_ascii_case("gzip"))
+        .unwrap_or(false)
+}
+
+/// Inflate a gzip-encoded response body.
+fn gunzip(compressed: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let mut decoder = GzDecoder::new(compressed);
+    let mut decompressed = Vec::new();
+    decoder.read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
 #[derive(Serialize)]
 pub struct DatasetApiListBuilder {
     #[serde(skip_serializing)]
@@ -170,7 +624,61 @@ impl DatasetApiListBuilder {
         self.sort = Some(sort.into());
         self
     }
+    /// Auto-paginate the dataset catalog, advancing `offset` by `limit`
+    /// after each page.
+    pub fn stream(&self) -> futures::stream::BoxStream<'static, Result<Dataset, Box<dyn std::error::Error + Send + Sync + 'static>>> {
+        let api = self.api.clone();
+        let sort = self.sort.clone();
+        let limit = self.limit.unwrap_or(50);
+        let offset = self.offset.unwrap_or(0);
+
+        if limit == 0 {
+            return futures::stream::once(async {
+                Err(Box::new(DomoErr("limit must be greater than 0".to_string()))
+                    as Box<dyn std::error::Error + Send + Sync + 'static>)
+            })
+            .boxed();
+        }
+
+        futures::stream::unfold(
+            (api, sort, limit, offset, false),
+            |(api, sort, limit, offset, done)| async move {
+                if done {
+                    return None;
+                }
+                let page = Self {
+                    api: api.clone(),
+                    limit: Some(limit),
+                    offset: Some(offset),
+                    sort: sort.clone(),
+                };
+                match page.execute().await {
+                    Ok(datasets) => {
+                        let is_last_page = datasets.len() < limit;
+                        Some((Ok(datasets), (api, sort, limit, offset + limit, is_last_page)))
+                    }
+                    Err(e) => Some((Err(e), (api, sort, limit, offset, true))),
+                }
+            },
+        )
+        .flat_map(|page| {
+            let items: Vec<Result<Dataset, Box<dyn std::error::Error + Send + Sync + 'static>>> = match page {
+                Ok(datasets) => datasets.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            futures::stream::iter(items)
+        })
+        .boxed()
+    }
+
+    /// Collect [`DatasetApiListBuilder::stream`] into a `Vec`.
+    pub async fn all(&self) -> Result<Vec<Dataset>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        use futures::TryStreamExt;
+        self.stream().try_collect().await
+    }
+
     pub async fn execute(&self) -> Result<Vec<Dataset>,Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.api.auth.require_scope(RequiredScope::Data)?;
         let token = self.api.auth.get_token().await?;
         let req = surf::get("https://api.domo.com/v1/datasets").query(self)?.header("Authorization", format!("Bearer {}", token));
         let s = self.api.client.send(req).await?.body_json().await?;
@@ -228,4 +736,59 @@ mod tests {
 
         })
     }
+
+    #[test]
+    fn test_gunzip_roundtrip() {
+        let original = b"id,name\n1,domo";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(original).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(gunzip(&compressed).unwrap(), original);
+    }
+
+    #[test]
+    fn test_pdp_filter_operator_serde_roundtrip() {
+        let cases = [
+            (PdpFilterOperator::Equals, "\"EQUALS\""),
+            (PdpFilterOperator::GreaterThan, "\"GREATER_THAN\""),
+            (PdpFilterOperator::Contains, "\"CONTAINS\""),
+        ];
+        for (operator, json) in cases {
+            assert_eq!(serde_json::to_string(&operator).unwrap(), json);
+            assert_eq!(serde_json::from_str::<PdpFilterOperator>(json).unwrap(), operator);
+        }
+    }
+
+    #[test]
+    fn test_column_type_serde_mapping() {
+        let cases = [
+            (ColumnType::String, "\"STRING\""),
+            (ColumnType::Long, "\"LONG\""),
+            (ColumnType::Decimal, "\"DECIMAL\""),
+            (ColumnType::Double, "\"DOUBLE\""),
+            (ColumnType::Date, "\"DATE\""),
+            (ColumnType::DateTime, "\"DATETIME\""),
+        ];
+        for (column_type, json) in cases {
+            assert_eq!(serde_json::to_string(&column_type).unwrap(), json);
+            assert_eq!(serde_json::from_str::<ColumnType>(json).unwrap(), column_type);
+        }
+    }
+
+    #[test]
+    fn test_pdp_filter_serde_roundtrip() {
+        let filter = PdpFilter {
+            column: "country".to_string(),
+            operator: PdpFilterOperator::Contains,
+            values: vec!["US".to_string(), "CA".to_string()],
+            not: true,
+        };
+        let json = serde_json::to_string(&filter).unwrap();
+        let round_tripped: PdpFilter = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.column, filter.column);
+        assert_eq!(round_tripped.operator, filter.operator);
+        assert_eq!(round_tripped.values, filter.values);
+        assert_eq!(round_tripped.not, filter.not);
+    }
 }
\ No newline at end of file